@@ -0,0 +1,23 @@
+use cfg_aliases::cfg_aliases;
+
+fn main() {
+    // The script doesn't depend on our code.
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // Mirrors the platform aliases winit's own build.rs defines for itself; we re-derive them
+    // here so the same `#[cfg(android_platform)]`-style gates are meaningful in this crate.
+    cfg_aliases! {
+        android_platform: { target_os = "android" },
+        ios_platform: { target_os = "ios" },
+        macos_platform: { target_os = "macos" },
+        windows_platform: { target_os = "windows" },
+        web_platform: { all(target_family = "wasm", target_os = "unknown") },
+        apple: { any(target_os = "ios", target_os = "macos") },
+        free_unix: { all(unix, not(apple), not(android_platform)) },
+
+        // Native displays; this crate doesn't gate them behind its own "x11"/"wayland" features
+        // the way winit does, so they're just aliases for "any free-standing Unix display".
+        x11_platform: { free_unix },
+        wayland_platform: { free_unix },
+    }
+}
@@ -2,23 +2,29 @@ use ::tracing::{error, info};
 #[cfg(not(any(android_platform, ios_platform)))]
 use raw_window_handle::{DisplayHandle, HasDisplayHandle};
 #[cfg(not(any(android_platform, ios_platform)))]
-use softbuffer::{Context, Surface};
+use softbuffer::Context;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::Debug;
-use std::num::NonZeroU32;
 #[cfg(not(any(android_platform, ios_platform)))]
 use std::sync::Arc;
 
 use winit::application::ApplicationHandler;
 use winit::dpi::{PhysicalPosition, PhysicalSize};
 use winit::event::{DeviceEvent, DeviceId, MouseScrollDelta, WindowEvent};
-use winit::event_loop::{ActiveEventLoop, EventLoop};
-use winit::window::{Icon, Window, WindowId};
+use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopClosed, EventLoopProxy};
+use winit::monitor::MonitorHandle;
+use winit::window::{Icon, Window, WindowId, WindowLevel};
 
+#[path = "renderer.rs"]
+mod renderer;
 #[path = "util/tracing.rs"]
 mod tracing;
 
+use renderer::{PixelsRenderer, Renderer};
+#[cfg(not(any(android_platform, ios_platform)))]
+use renderer::SoftbufferRenderer;
+
 fn main() -> Result<(), Box<dyn Error>> {
     tracing::init();
 
@@ -37,15 +43,118 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     });
 
-    let mut state = Application::new(&event_loop);
+    let mut state = Application::new(&event_loop, OverlayConfig::default());
 
     event_loop.run_app(&mut state).map_err(Into::into)
 }
 
+/// Compositor-facing identity and behavior for the overlay's windows.
+#[derive(Debug, Clone)]
+pub struct OverlayConfig {
+    /// Window title, shown in window switchers/taskbars where the compositor doesn't hide it.
+    pub title: String,
+    /// Application identifier: `WM_CLASS` on X11, the `app_id` on Wayland.
+    pub app_id: String,
+    /// Whether windows should be created always-on-top at `window_level`.
+    pub always_on_top: bool,
+    /// Passed to `set_cursor_hittest`: `false` makes windows click-through, `true` lets them
+    /// receive cursor input.
+    pub cursor_hittest: bool,
+    /// Window level to request when `always_on_top` is set.
+    pub window_level: WindowLevel,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            app_id: "overlay".to_owned(),
+            always_on_top: true,
+            cursor_hittest: false,
+            window_level: WindowLevel::AlwaysOnTop,
+        }
+    }
+}
+
+/// The overlay's rendered scene: a row-major `0xAARRGGBB` pixel buffer authored at `width`x`height`
+/// logical pixels. `WindowState::draw` nearest-neighbor scales it onto each window's physical
+/// framebuffer using that window's current scale factor.
+#[derive(Debug, Clone)]
+pub struct Scene {
+    /// Width in logical pixels.
+    pub width: u32,
+    /// Height in logical pixels.
+    pub height: u32,
+    /// Row-major `0xAARRGGBB` pixels, `width * height` long.
+    pub pixels: std::sync::Arc<[u32]>,
+}
+
+/// Commands accepted by [`Application::user_event`], pushed from any thread via an
+/// [`OverlayHandle`].
 #[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-enum UserEvent {
+#[derive(Debug, Clone)]
+pub enum UserEvent {
+    /// Wake the event loop without taking any other action; no redraw is requested.
     WakeUp,
+    /// Make every overlay window visible.
+    Show,
+    /// Hide every overlay window without destroying it.
+    Hide,
+    /// Set the overlay's opacity, applied to every window on the next redraw.
+    SetOpacity(f32),
+    /// Tear down the current overlay windows and re-span a single one over the monitor at the
+    /// given index in `available_monitors()` order.
+    MoveToMonitor(usize),
+    /// Replace the rendered scene; consumed by `WindowState::draw` on the next redraw.
+    UpdateScene(Scene),
+    /// Exit the event loop.
+    Shutdown,
+}
+
+/// A thread-safe handle for driving the overlay from any thread.
+///
+/// Wraps the [`EventLoopProxy`] returned by [`EventLoop::create_proxy`]; each method queues a
+/// [`UserEvent`] for the main loop to pick up on its next iteration.
+#[derive(Clone)]
+pub struct OverlayHandle {
+    proxy: EventLoopProxy<UserEvent>,
+}
+
+impl OverlayHandle {
+    /// Wrap an `EventLoopProxy` obtained from `EventLoop::create_proxy`.
+    pub fn new(proxy: EventLoopProxy<UserEvent>) -> Self {
+        Self { proxy }
+    }
+
+    /// Make every overlay window visible.
+    pub fn show(&self) -> Result<(), EventLoopClosed<UserEvent>> {
+        self.proxy.send_event(UserEvent::Show)
+    }
+
+    /// Hide every overlay window without destroying it.
+    pub fn hide(&self) -> Result<(), EventLoopClosed<UserEvent>> {
+        self.proxy.send_event(UserEvent::Hide)
+    }
+
+    /// Set the overlay's opacity.
+    pub fn set_opacity(&self, opacity: f32) -> Result<(), EventLoopClosed<UserEvent>> {
+        self.proxy.send_event(UserEvent::SetOpacity(opacity))
+    }
+
+    /// Re-span the overlay over the monitor at `index` in `available_monitors()` order.
+    pub fn move_to_monitor(&self, index: usize) -> Result<(), EventLoopClosed<UserEvent>> {
+        self.proxy.send_event(UserEvent::MoveToMonitor(index))
+    }
+
+    /// Push a new scene for the overlay to present on its next redraw.
+    pub fn update_scene(&self, scene: Scene) -> Result<(), EventLoopClosed<UserEvent>> {
+        self.proxy.send_event(UserEvent::UpdateScene(scene))
+    }
+
+    /// Shut the overlay down.
+    pub fn shutdown(&self) -> Result<(), EventLoopClosed<UserEvent>> {
+        self.proxy.send_event(UserEvent::Shutdown)
+    }
 }
 
 /// Application state and event handling.
@@ -56,10 +165,19 @@ struct Application {
     /// Drawing context.
     ///
     context: Option<Context<DisplayHandle<'static>>>,
+    /// Opacity last requested via `UserEvent::SetOpacity`, applied to every window.
+    opacity: f32,
+    /// Scene last pushed via `UserEvent::UpdateScene`, presented by `WindowState::draw`.
+    scene: Option<Scene>,
+    /// Compositor-facing identity and behavior applied to every window this app creates.
+    config: OverlayConfig,
+    /// Set by `UserEvent::MoveToMonitor`; while `Some`, `about_to_wait`'s hot-plug reconciliation
+    /// keeps the overlay on this single monitor instead of re-spanning every available one.
+    pinned_monitor: Option<MonitorHandle>,
 }
 
 impl Application {
-    fn new<T>(event_loop: &EventLoop<T>) -> Self {
+    fn new<T>(event_loop: &EventLoop<T>, config: OverlayConfig) -> Self {
         // SAFETY: we drop the context right before the event loop is stopped, thus making it safe.
         let context = Some(
             Context::new(unsafe {
@@ -82,36 +200,72 @@ impl Application {
             context,
             icon,
             windows: Default::default(),
+            opacity: 1.0,
+            scene: None,
+            config,
+            pinned_monitor: None,
         }
     }
 
+    /// Create one borderless overlay window spanning `monitor`, with its window level and
+    /// click-through behavior taken from `self.config`.
     fn create_window(
         &mut self,
         event_loop: &ActiveEventLoop,
-        _tab_id: Option<String>,
+        monitor: MonitorHandle,
     ) -> Result<WindowId, Box<dyn Error>> {
         // TODO read-out activation token.
 
         #[allow(unused_mut)]
         let mut window_attributes = Window::default_attributes()
-            .with_title("")
+            .with_title(self.config.title.clone())
             .with_transparent(true)
-            .with_fullscreen(Some(winit::window::Fullscreen::Borderless(None)));
+            .with_fullscreen(Some(winit::window::Fullscreen::Borderless(Some(
+                monitor.clone(),
+            ))));
+
+        // WM_CLASS on X11, the `app_id` on Wayland. Both cfgs can be true at once (there's no
+        // separate "x11"/"wayland" feature here, just free_unix), so each extension trait needs
+        // its own block: both `with_name`s share a signature, making a single call ambiguous.
+        #[cfg(x11_platform)]
+        {
+            use winit::platform::x11::WindowAttributesExtX11;
+            window_attributes =
+                window_attributes.with_name(&self.config.app_id, &self.config.app_id);
+        }
+        #[cfg(wayland_platform)]
+        {
+            use winit::platform::wayland::WindowAttributesExtWayland;
+            window_attributes =
+                window_attributes.with_name(&self.config.app_id, &self.config.app_id);
+        }
 
         let window = event_loop.create_window(window_attributes)?;
 
-        window.set_window_level(winit::window::WindowLevel::AlwaysOnTop);
+        if self.config.always_on_top {
+            window.set_window_level(self.config.window_level);
+        }
         window
-            .set_cursor_hittest(false)
-            .expect("Failed to disable cursor hit test");
+            .set_cursor_hittest(self.config.cursor_hittest)
+            .expect("Failed to set cursor hit test");
 
-        let window_state = WindowState::new(self, window)?;
+        let window_state = WindowState::new(self, window, monitor)?;
         let window_id = window_state.window.id();
         info!("Created new window with id={window_id:?}");
         self.windows.insert(window_id, window_state);
         Ok(window_id)
     }
 
+    /// Rebuild the renderer for `window_id` from its retained `Window`, e.g. after an
+    /// Android/iOS `resumed` that follows a `suspended` teardown.
+    #[cfg(any(android_platform, ios_platform))]
+    fn recreate_renderer(&mut self, window_id: WindowId) -> Result<(), Box<dyn Error>> {
+        let window = Arc::clone(&self.windows.get(&window_id).unwrap().window);
+        let renderer = build_renderer(self, Arc::clone(&window), window.inner_size())?;
+        self.windows.get_mut(&window_id).unwrap().renderer = Some(renderer);
+        Ok(())
+    }
+
     fn dump_monitors(&self, event_loop: &ActiveEventLoop) {
         info!("Monitors information");
         let primary_monitor = event_loop.primary_monitor();
@@ -159,8 +313,51 @@ impl Application {
 }
 
 impl ApplicationHandler<UserEvent> for Application {
-    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: UserEvent) {
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: UserEvent) {
         info!("User event: {event:?}");
+
+        match event {
+            UserEvent::WakeUp => {}
+            UserEvent::Show => {
+                for window in self.windows.values() {
+                    window.window.set_visible(true);
+                }
+            }
+            UserEvent::Hide => {
+                for window in self.windows.values() {
+                    window.window.set_visible(false);
+                }
+            }
+            UserEvent::SetOpacity(opacity) => {
+                self.opacity = opacity;
+                for window in self.windows.values() {
+                    window.window.request_redraw();
+                }
+            }
+            UserEvent::MoveToMonitor(index) => {
+                let Some(monitor) = event_loop.available_monitors().nth(index) else {
+                    error!("MoveToMonitor: no monitor at index {index}");
+                    return;
+                };
+                // Pin to this monitor so the hot-plug reconciliation in `about_to_wait` doesn't
+                // immediately re-span every other connected monitor.
+                self.pinned_monitor = Some(monitor.clone());
+                self.windows.clear();
+                if let Err(err) = self.create_window(event_loop, monitor) {
+                    error!("Failed to create window for MoveToMonitor: {err}");
+                }
+            }
+            UserEvent::UpdateScene(scene) => {
+                self.scene = Some(scene);
+                for window in self.windows.values() {
+                    window.window.request_redraw();
+                }
+            }
+            UserEvent::Shutdown => {
+                info!("Shutdown requested over the command channel");
+                event_loop.exit();
+            }
+        }
     }
 
     fn window_event(
@@ -169,6 +366,8 @@ impl ApplicationHandler<UserEvent> for Application {
         window_id: WindowId,
         event: WindowEvent,
     ) {
+        let scene = self.scene.clone();
+        let opacity = self.opacity;
         let window = match self.windows.get_mut(&window_id) {
             Some(window) => window,
             None => return,
@@ -183,7 +382,7 @@ impl ApplicationHandler<UserEvent> for Application {
                 }
             }
             WindowEvent::RedrawRequested => {
-                if let Err(err) = window.draw() {
+                if let Err(err) = window.draw(scene.as_ref(), opacity) {
                     error!("Error drawing window: {err}");
                 }
             }
@@ -191,6 +390,21 @@ impl ApplicationHandler<UserEvent> for Application {
                 info!("Closing Window={window_id:?}");
                 self.windows.remove(&window_id);
             }
+            WindowEvent::Resized(size) => {
+                if let Err(err) = window.resize_surface(size) {
+                    error!("Error resizing window={window_id:?}: {err}");
+                }
+                window.window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                info!("Window={window_id:?} scale factor changed to {scale_factor}");
+                window.scale_factor = scale_factor;
+                let size = window.window.inner_size();
+                if let Err(err) = window.resize_surface(size) {
+                    error!("Error resizing window={window_id:?} after scale change: {err}");
+                }
+                window.window.request_redraw();
+            }
             WindowEvent::MouseWheel { delta, .. } => match delta {
                 MouseScrollDelta::LineDelta(x, y) => {
                     info!("Mouse wheel Line Delta: ({x},{y})");
@@ -224,12 +438,89 @@ impl ApplicationHandler<UserEvent> for Application {
         info!("Resumed the event loop");
         self.dump_monitors(event_loop);
 
-        // Create initial window.
-        self.create_window(event_loop, None)
-            .expect("failed to create initial window");
+        #[cfg(any(android_platform, ios_platform))]
+        {
+            // Windows that survived a suspend keep their logical `Window`, but their renderer
+            // was torn down; rebuild it now that the native surface can exist again.
+            let suspended: Vec<WindowId> = self
+                .windows
+                .iter()
+                .filter(|(_, window)| window.renderer.is_none())
+                .map(|(window_id, _)| *window_id)
+                .collect();
+            for window_id in suspended {
+                if let Err(err) = self.recreate_renderer(window_id) {
+                    error!("Failed to recreate renderer for window={window_id:?}: {err}");
+                }
+            }
+            if !self.windows.is_empty() {
+                return;
+            }
+        }
+
+        // Span one overlay window across every currently connected monitor.
+        for monitor in event_loop.available_monitors() {
+            self.create_window(event_loop, monitor)
+                .expect("failed to create window for monitor");
+        }
+    }
+
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        info!("Suspended the event loop");
+
+        // On Android/iOS the native surface is destroyed while the app is backgrounded; drop
+        // each window's renderer but keep the logical `Window` so `resumed` can rebuild it.
+        #[cfg(any(android_platform, ios_platform))]
+        for window in self.windows.values_mut() {
+            window.renderer = None;
+        }
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let current_monitors: Vec<MonitorHandle> = event_loop.available_monitors().collect();
+
+        if let Some(pinned) = self.pinned_monitor.clone() {
+            if !current_monitors.contains(&pinned) {
+                info!("Pinned monitor detached, resuming multi-monitor span");
+                self.pinned_monitor = None;
+            } else {
+                // While pinned, only ever keep the one window on `pinned` around.
+                let already_covered = self.windows.values().any(|w| w.monitor == pinned);
+                if !already_covered {
+                    if let Err(err) = self.create_window(event_loop, pinned.clone()) {
+                        error!("Failed to create window for pinned monitor: {err}");
+                    }
+                }
+                self.windows.retain(|_, window| window.monitor == pinned);
+
+                if self.windows.is_empty() {
+                    info!("No windows left, exiting...");
+                    event_loop.exit();
+                }
+                return;
+            }
+        }
+
+        // Spawn a window for any monitor that's appeared since the last check.
+        for monitor in &current_monitors {
+            let already_covered = self.windows.values().any(|w| &w.monitor == monitor);
+            if !already_covered {
+                info!("Monitor {monitor:?} attached, creating overlay window");
+                if let Err(err) = self.create_window(event_loop, monitor.clone()) {
+                    error!("Failed to create window for new monitor: {err}");
+                }
+            }
+        }
+
+        // Drop windows whose monitor has gone away.
+        self.windows.retain(|window_id, window| {
+            let still_present = current_monitors.contains(&window.monitor);
+            if !still_present {
+                info!("Monitor for window={window_id:?} detached, dropping overlay window");
+            }
+            still_present
+        });
+
         if self.windows.is_empty() {
             info!("No windows left, exiting...");
             event_loop.exit();
@@ -242,55 +533,118 @@ impl ApplicationHandler<UserEvent> for Application {
     }
 }
 
+/// Build the renderer for a window, preferring the GPU-composited [`PixelsRenderer`] and falling
+/// back to [`SoftbufferRenderer`] on machines without a usable GPU adapter.
+#[cfg(not(any(android_platform, ios_platform)))]
+fn build_renderer(
+    app: &Application,
+    window: Arc<Window>,
+    size: PhysicalSize<u32>,
+) -> Result<Box<dyn Renderer>, Box<dyn Error>> {
+    match PixelsRenderer::new(Arc::clone(&window), size) {
+        Ok(renderer) => Ok(Box::new(renderer)),
+        Err(err) => {
+            info!("No usable GPU adapter for pixels ({err}), falling back to softbuffer");
+            let renderer = SoftbufferRenderer::new(app.context.as_ref().unwrap(), window, size)?;
+            Ok(Box::new(renderer))
+        }
+    }
+}
+
+/// Build the renderer for a window. `SoftbufferRenderer` isn't available on Android/iOS, so
+/// these platforms always use `PixelsRenderer`.
+#[cfg(any(android_platform, ios_platform))]
+fn build_renderer(
+    _app: &Application,
+    window: Arc<Window>,
+    size: PhysicalSize<u32>,
+) -> Result<Box<dyn Renderer>, Box<dyn Error>> {
+    Ok(Box::new(PixelsRenderer::new(window, size)?))
+}
+
 /// State of the window.
 struct WindowState {
-    /// Render surface.
-    /// NOTE: This surface must be dropped before the `Window`.
-    #[cfg(not(any(android_platform, ios_platform)))]
-    surface: Surface<DisplayHandle<'static>, Arc<Window>>,
+    /// Presentation backend for this window.
+    ///
+    /// `None` while suspended on Android/iOS: the native surface is destroyed when the app is
+    /// backgrounded, so the renderer is dropped in `suspended` and rebuilt from the retained
+    /// `window` once `resumed` fires again.
+    ///
+    /// NOTE: This must be dropped before `window`.
+    renderer: Option<Box<dyn Renderer>>,
     /// The actual winit Window.
     window: Arc<Window>,
+    /// The monitor this window is spanning.
+    monitor: MonitorHandle,
+    /// Scale factor in effect the last time this window was sized. Used by `draw` to map a
+    /// `Scene`'s logical pixels onto this window's physical framebuffer.
+    scale_factor: f64,
 }
 
 impl WindowState {
-    fn new(app: &Application, window: Window) -> Result<Self, Box<dyn Error>> {
+    fn new(
+        app: &Application,
+        window: Window,
+        monitor: MonitorHandle,
+    ) -> Result<Self, Box<dyn Error>> {
         let window = Arc::new(window);
 
-        // SAFETY: the surface is dropped before the `window` which provided it with handle, thus
-        // it doesn't outlive it.
-        let mut surface = Surface::new(app.context.as_ref().unwrap(), Arc::clone(&window))?;
-
-        let (width, height) = match (
-            NonZeroU32::new(window.inner_size().width),
-            NonZeroU32::new(window.inner_size().height),
-        ) {
-            (Some(width), Some(height)) => (width, height),
-            _ => {
-                return Err(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "Failed to resize inner buffer",
-                )))
-            }
-        };
-        surface
-            .resize(width, height)
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error>);
+        let renderer = build_renderer(app, Arc::clone(&window), window.inner_size())?;
+        let scale_factor = window.scale_factor();
 
         let state = Self {
-            #[cfg(not(any(android_platform, ios_platform)))]
-            surface,
+            renderer: Some(renderer),
             window,
+            monitor,
+            scale_factor,
         };
 
         Ok(state)
     }
 
-    /// Draw the window contents.
-    fn draw(&mut self) -> Result<(), Box<dyn Error>> {
-        let buffer = self.surface.buffer_mut()?;
+    /// Recompute the physical buffer size from `size` and resize the renderer to match.
+    ///
+    /// Called on `Resized` and after a `ScaleFactorChanged` event, since the buffer is always
+    /// sized in physical pixels regardless of the window's logical size. A no-op while
+    /// `renderer` is `None` (suspended).
+    fn resize_surface(&mut self, size: PhysicalSize<u32>) -> Result<(), Box<dyn Error>> {
+        match &mut self.renderer {
+            Some(renderer) => renderer.resize(size),
+            None => Ok(()),
+        }
+    }
+
+    /// Draw `scene` into the window at `opacity`, nearest-neighbor scaling its logical pixels
+    /// onto this window's physical framebuffer. A no-op while `renderer` is `None` (suspended).
+    fn draw(&mut self, scene: Option<&Scene>, opacity: f32) -> Result<(), Box<dyn Error>> {
         self.window.pre_present_notify();
-        buffer.present()?;
-        Ok(())
+        if self.renderer.is_none() {
+            return Ok(());
+        }
+        let physical = self.window.inner_size();
+        let buffer = match scene {
+            Some(scene) => self.scale_to_physical(scene, physical),
+            None => vec![0u32; (physical.width * physical.height) as usize],
+        };
+        self.renderer.as_mut().unwrap().present(&buffer, opacity)
+    }
+
+    /// Nearest-neighbor scale `scene`'s logical pixels onto a `physical`-sized buffer, using
+    /// `self.scale_factor` to map each physical pixel back to its logical source.
+    fn scale_to_physical(&self, scene: &Scene, physical: PhysicalSize<u32>) -> Vec<u32> {
+        let mut buffer = vec![0u32; (physical.width * physical.height) as usize];
+        if scene.width == 0 || scene.height == 0 {
+            return buffer;
+        }
+        for y in 0..physical.height {
+            let src_y = ((y as f64 / self.scale_factor) as u32).min(scene.height - 1);
+            let row = (src_y * scene.width) as usize;
+            for x in 0..physical.width {
+                let src_x = ((x as f64 / self.scale_factor) as u32).min(scene.width - 1);
+                buffer[(y * physical.width + x) as usize] = scene.pixels[row + src_x as usize];
+            }
+        }
+        buffer
     }
 }
 
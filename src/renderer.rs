@@ -0,0 +1,113 @@
+use std::error::Error;
+#[cfg(not(any(android_platform, ios_platform)))]
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use pixels::{wgpu::Color, Pixels, SurfaceTexture};
+#[cfg(not(any(android_platform, ios_platform)))]
+use raw_window_handle::DisplayHandle;
+#[cfg(not(any(android_platform, ios_platform)))]
+use softbuffer::{Context, Surface};
+use winit::dpi::PhysicalSize;
+use winit::window::Window;
+
+/// Backend-agnostic presentation surface for a single overlay window.
+pub trait Renderer {
+    /// Resize the presentation surface to `size` physical pixels.
+    fn resize(&mut self, size: PhysicalSize<u32>) -> Result<(), Box<dyn Error>>;
+
+    /// Present `scene` (`0xAARRGGBB` pixels, row-major, sized to the last `resize`), scaling
+    /// alpha by `opacity`.
+    fn present(&mut self, scene: &[u32], opacity: f32) -> Result<(), Box<dyn Error>>;
+}
+
+/// Scale `argb`'s alpha channel by `opacity`, clamped to `[0.0, 1.0]`.
+fn scale_alpha(argb: u32, opacity: f32) -> u32 {
+    let alpha = (argb >> 24) & 0xff;
+    let scaled = (alpha as f32 * opacity.clamp(0.0, 1.0)).round() as u32;
+    (scaled << 24) | (argb & 0x00ff_ffff)
+}
+
+/// CPU-rendered backend built on `softbuffer`.
+#[cfg(not(any(android_platform, ios_platform)))]
+pub struct SoftbufferRenderer {
+    // NOTE: This surface must be dropped before the `Window`.
+    surface: Surface<DisplayHandle<'static>, Arc<Window>>,
+}
+
+#[cfg(not(any(android_platform, ios_platform)))]
+impl SoftbufferRenderer {
+    /// Create a renderer presenting into `window` via `context`, sized to `size`.
+    pub fn new(
+        context: &Context<DisplayHandle<'static>>,
+        window: Arc<Window>,
+        size: PhysicalSize<u32>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let surface = Surface::new(context, window)?;
+        let mut renderer = Self { surface };
+        renderer.resize(size)?;
+        Ok(renderer)
+    }
+}
+
+#[cfg(not(any(android_platform, ios_platform)))]
+impl Renderer for SoftbufferRenderer {
+    fn resize(&mut self, size: PhysicalSize<u32>) -> Result<(), Box<dyn Error>> {
+        let (width, height) = match (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) {
+            (Some(width), Some(height)) => (width, height),
+            // The window is minimized or not yet mapped; nothing to resize.
+            _ => return Ok(()),
+        };
+        self.surface.resize(width, height)?;
+        Ok(())
+    }
+
+    fn present(&mut self, scene: &[u32], opacity: f32) -> Result<(), Box<dyn Error>> {
+        let mut buffer = self.surface.buffer_mut()?;
+        let len = buffer.len().min(scene.len());
+        for (dst, &src) in buffer[..len].iter_mut().zip(scene.iter()) {
+            *dst = scale_alpha(src, opacity);
+        }
+        buffer.present()?;
+        Ok(())
+    }
+}
+
+/// GPU-composited backend built on `pixels` (wgpu).
+pub struct PixelsRenderer {
+    pixels: Pixels,
+}
+
+impl PixelsRenderer {
+    /// Create a renderer presenting into `window`, sized to `size`.
+    pub fn new(window: Arc<Window>, size: PhysicalSize<u32>) -> Result<Self, Box<dyn Error>> {
+        let width = size.width.max(1);
+        let height = size.height.max(1);
+        let surface_texture = SurfaceTexture::new(width, height, window.as_ref());
+        let mut pixels = Pixels::new(width, height, surface_texture)?;
+        pixels.clear_color(Color::TRANSPARENT);
+        Ok(Self { pixels })
+    }
+}
+
+impl Renderer for PixelsRenderer {
+    fn resize(&mut self, size: PhysicalSize<u32>) -> Result<(), Box<dyn Error>> {
+        if size.width == 0 || size.height == 0 {
+            // The window is minimized; pixels doesn't like being resized to zero.
+            return Ok(());
+        }
+        self.pixels.resize_surface(size.width, size.height)?;
+        self.pixels.resize_buffer(size.width, size.height)?;
+        Ok(())
+    }
+
+    fn present(&mut self, scene: &[u32], opacity: f32) -> Result<(), Box<dyn Error>> {
+        let frame = self.pixels.frame_mut();
+        for (px, argb) in frame.chunks_exact_mut(4).zip(scene.iter()) {
+            let [a, r, g, b] = scale_alpha(*argb, opacity).to_be_bytes();
+            px.copy_from_slice(&[r, g, b, a]);
+        }
+        self.pixels.render()?;
+        Ok(())
+    }
+}